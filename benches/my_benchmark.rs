@@ -1,5 +1,8 @@
-use criterion::{criterion_group, criterion_main, Criterion, black_box};
-use subspace_assignment::{inverse_sqrt, sqrt_permutation, run, from_block, gen_largest_prime, PRIME_BYTE_SIZE, Block};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, black_box};
+use subspace_assignment::{
+    decode_iterated, encode_iterated, inverse_sqrt, sqrt_permutation, run, from_block,
+    gen_largest_prime, PRIME_BYTE_SIZE, Block,
+};
 use rug::{Integer};
 
 fn encode_decode(c: &mut Criterion) {
@@ -25,13 +28,38 @@ fn encode_decode(c: &mut Criterion) {
 }
 
 fn end_to_end(c: &mut Criterion) {
-    c.bench_function("end_to_end", |b| b.iter(|| 
+    c.bench_function("end_to_end", |b| b.iter(||
         black_box(run())
     ));
 }
-  
 
-criterion_group!(benches, end_to_end, encode_decode);
+// charts the linear delay growth of the iterated construction: encode cost
+// should scale with tau, while decode stays comparatively cheap
+fn iterated(c: &mut Criterion) {
+    let prime = gen_largest_prime(PRIME_BYTE_SIZE);
+
+    // e = (p + 1) / 4
+    let mut exponent = prime.clone() + Integer::from(1);
+    exponent.div_exact_u_mut(4);
+
+    let block_in: Block = rand::random();
+    let int = from_block(block_in);
+
+    let mut group = c.benchmark_group("iterated");
+    for tau in [1u32, 4, 16, 64] {
+        group.bench_with_input(BenchmarkId::new("encode", tau), &tau, |b, &tau| {
+            b.iter(|| black_box(encode_iterated(&int, &prime, &exponent, tau)))
+        });
+
+        let encoded = encode_iterated(&int, &prime, &exponent, tau);
+        group.bench_with_input(BenchmarkId::new("decode", tau), &tau, |b, &tau| {
+            b.iter(|| black_box(decode_iterated(&encoded, &prime, tau)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, end_to_end, encode_decode, iterated);
 criterion_main!(benches);
 
 // paper: C = 77.922
@@ -1,9 +1,12 @@
 // rug is the Rust equivalent of GMP (GNU Multi Precision Arithmetic Library)
 // so this is the equiv of "#include <gmp.h>" in the pysloth implementation
 // we use this since Rust does not support 256bit integers
+use rand_core::RngCore;
 use rug::ops::NegAssign;
+use rug::rand::{ThreadRandGen, ThreadRandState};
 use rug::{integer::IsPrime, integer::Order, Assign, Integer};
 use std::ops::AddAssign;
+use std::sync::OnceLock;
 
 // for a prime size of 256 bits, derive the LARGEST prime and resulting exponent
 // for a given input block of 256 bits (akin the the plaintext), compute the modular square root (akin to the ciphertext)
@@ -14,8 +17,6 @@ use std::ops::AddAssign;
 pub const BLOCK_BYTE_SIZE: usize = 32;
 // prime size of 256 bits
 pub const PRIME_BYTE_SIZE: usize = 32;
-// num iterations used in pysloth implementation (https://github.com/randomchain/pysloth/blob/master/sloth.c)
-const PRIME_CHECK_ITERS: u32 = 25;
 const ORDER: Order = Order::Lsf;
 pub type Block = [u8; BLOCK_BYTE_SIZE];
 
@@ -31,44 +32,388 @@ pub fn to_block(int: Integer) -> Block {
     block
 }
 
-/// find next lowset prime above a given value
-pub fn next_prime(p: &mut Integer) {
+// all primes below this bound are used as a cheap trial-division pre-sieve;
+// each prime (and so each residue kept against it) fits in a u32, and together
+// they reject the large majority of composite candidates before paying for
+// the full primality check
+const SMALL_PRIME_BOUND: u32 = 1 << 16;
+
+/// small primes below `SMALL_PRIME_BOUND`, sieved once and cached for the
+/// lifetime of the process
+fn small_primes() -> &'static [u32] {
+    static SMALL_PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    SMALL_PRIMES.get_or_init(|| sieve_of_eratosthenes(SMALL_PRIME_BOUND))
+}
+
+/// classic sieve of Eratosthenes, used once at startup to build `small_primes`
+fn sieve_of_eratosthenes(bound: u32) -> Vec<u32> {
+    let bound = bound as usize;
+    let mut is_composite = vec![false; bound];
+    let mut primes = Vec::new();
+    for n in 2..bound {
+        if !is_composite[n] {
+            primes.push(n as u32);
+            let mut m = n * n;
+            while m < bound {
+                is_composite[m] = true;
+                m += n;
+            }
+        }
+    }
+    primes
+}
+
+/// residues `candidate mod p_i` for each small prime `p_i`, kept up to date as
+/// `candidate` is advanced so a sieve rejection never needs a fresh `mod_u`
+fn sieve_residues(candidate: &Integer, primes: &[u32]) -> Vec<u32> {
+    primes.iter().map(|&p| candidate.mod_u(p)).collect()
+}
+
+/// advances residues in lock-step with a candidate that was just shifted by
+/// `delta` (+2 scanning upward, -2 scanning downward, -4 for the safe-prime
+/// scan over `p`)
+fn advance_sieve_residues(residues: &mut [u32], primes: &[u32], delta: i32) {
+    for (r, &p) in residues.iter_mut().zip(primes) {
+        *r = ((*r as i64 + delta as i64).rem_euclid(p as i64)) as u32;
+    }
+}
+
+/// true if `candidate` is divisible by any of the small sieve primes, i.e. it
+/// can be rejected without running the (much costlier) Miller-Rabin test
+fn rejected_by_sieve(residues: &[u32]) -> bool {
+    residues.contains(&0)
+}
+
+/// true if `candidate` is itself one of the sieve's own small primes, so a
+/// zero residue against itself must not be mistaken for a composite rejection
+fn is_small_prime_candidate(candidate: &Integer, primes: &[u32]) -> bool {
+    match candidate.to_u32() {
+        Some(v) if v < SMALL_PRIME_BOUND => primes.binary_search(&v).is_ok(),
+        _ => false,
+    }
+}
+
+/// base-2 strong Miller-Rabin round: `n` is assumed odd and greater than 2
+fn strong_probable_prime_base2(n: &Integer) -> bool {
+    let n_minus_1 = Integer::from(n - 1);
+    // write n - 1 = d * 2^s with d odd
+    let s = n_minus_1.find_one(0).unwrap_or(0);
+    let d = Integer::from(&n_minus_1 >> s);
+
+    let mut x = match Integer::from(2).pow_mod(&d, n) {
+        Ok(x) => x,
+        Err(_) => unreachable!(),
+    };
+    if x == 1 || x == n_minus_1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = match x.pow_mod(&Integer::from(2), n) {
+            Ok(x) => x,
+            Err(_) => unreachable!(),
+        };
+        if x == n_minus_1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// finds the first `D` in 5, -7, 9, -11, ... with Jacobi symbol `(D/n) = -1`,
+/// returning `D` along with the matching Lucas parameters `P = 1`,
+/// `Q = (1 - D) / 4`; `None` only if `n` is a perfect square, in which case no
+/// such `D` exists
+fn choose_lucas_d(n: &Integer) -> Option<(Integer, Integer)> {
+    if n.is_perfect_square() {
+        return None;
+    }
+    let mut d: i64 = 5;
+    loop {
+        let d_int = Integer::from(d);
+        if d_int.jacobi(n) == -1 {
+            let q = Integer::from(1 - &d_int) / 4;
+            return Some((d_int, q));
+        }
+        d = if d > 0 { -(d + 2) } else { -(d - 2) };
+    }
+}
+
+/// strong Lucas probable-prime test with parameters `P = 1`,
+/// `Q = (1 - D) / 4`; `n` is assumed odd, greater than 2, and not a perfect
+/// square
+fn strong_lucas_probable_prime(n: &Integer, d: &Integer, q: &Integer) -> bool {
+    // n + 1 = k * 2^s with k odd
+    let mut k = Integer::from(n + 1);
+    let s = k.find_one(0).unwrap_or(0);
+    k >>= s;
+
+    let inv2 = match Integer::from(2).invert(n) {
+        Ok(inv2) => inv2,
+        Err(_) => unreachable!(),
+    };
+
+    // walk the bits of k (MSB first) computing (U_k, V_k, Q^k) mod n via the
+    // standard Lucas doubling ladder, starting from (U_1, V_1, Q^1)
+    let bits = k.significant_bits();
+    let (mut u, mut v, mut qk) = (Integer::from(1), Integer::from(1), Integer::from(q % n));
+    for i in (0..bits - 1).rev() {
+        let u2 = Integer::from(&u * &v) % n;
+        let v2 = (Integer::from(&v * &v) - Integer::from(2 * &qk)) % n;
+        let qk2 = Integer::from(&qk * &qk) % n;
+
+        if k.get_bit(i) {
+            u = Integer::from(&u2 + &v2) * &inv2 % n;
+            v = (Integer::from(d * &u2) + &v2) * &inv2 % n;
+            qk = Integer::from(&qk2 * q) % n;
+        } else {
+            u = u2;
+            v = v2;
+            qk = qk2;
+        }
+    }
+
+    if u == 0 {
+        return true;
+    }
+    if v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = (Integer::from(&v * &v) - Integer::from(2 * &qk)) % n;
+        if v == 0 {
+            return true;
+        }
+        qk = Integer::from(&qk * &qk) % n;
+    }
+    false
+}
+
+/// Baillie-PSW probable-prime test: a base-2 strong Miller-Rabin round
+/// combined with a strong Lucas test. No composite is currently known to pass
+/// both, making this a much stronger guarantee than a fixed-round
+/// Miller-Rabin test alone.
+pub fn is_prime_bpsw(n: &Integer) -> bool {
+    if *n < 2 {
+        return false;
+    }
+    if *n == 2 {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+    let primes = small_primes();
+    if is_small_prime_candidate(n, primes) {
+        return true;
+    }
+    if primes.iter().any(|&p| n.is_divisible_u(p)) {
+        return false;
+    }
+
+    strong_probable_prime_base2(n)
+        && match choose_lucas_d(n) {
+            Some((d, q)) => strong_lucas_probable_prime(n, &d, &q),
+            None => false,
+        }
+}
+
+/// selects which primality test `next_prime_with`/`prev_prime_with` and the
+/// `_with` prime-generation entry points run once the small-prime sieve has
+/// let a candidate through
+pub enum PrimalityCheck {
+    /// one base-2 strong Miller-Rabin round plus a strong Lucas test (see
+    /// [`is_prime_bpsw`]) — the fast mode, no known composite passes both
+    Bpsw,
+    /// plain Miller-Rabin with the given round count — the paranoid mode,
+    /// each round bounds the worst-case false-positive probability by 1/4
+    MillerRabin(u32),
+}
+
+impl PrimalityCheck {
+    fn is_prime(&self, n: &Integer) -> bool {
+        match self {
+            PrimalityCheck::Bpsw => is_prime_bpsw(n),
+            PrimalityCheck::MillerRabin(rounds) => n.is_probably_prime(*rounds) != IsPrime::No,
+        }
+    }
+}
+
+/// returns a Miller-Rabin round count giving a false-positive probability of
+/// at most `2^-error_bits` for a randomly generated candidate of `bits` bits.
+///
+/// `error_bits / 2` rounds (rounded up) already meet this bound in the
+/// adversarial worst case, since each round bounds the error by 1/4. For
+/// candidates drawn at random rather than chosen adversarially, the
+/// average-case error shrinks quickly as the candidate grows (the basis for
+/// the bit-size-dependent round counts in e.g. FIPS 186-4 appendix C.3), so
+/// this caps the worst-case figure once `bits` is large enough for that to
+/// matter.
+pub fn mr_rounds_for_error_bound(bits: u32, error_bits: u32) -> u32 {
+    let worst_case_rounds = error_bits.div_ceil(2).max(1);
+    let average_case_cap = match bits {
+        0..=256 => u32::MAX,
+        257..=512 => 12,
+        513..=1024 => 6,
+        _ => 3,
+    };
+    worst_case_rounds.min(average_case_cap).max(2)
+}
+
+/// find next lowset prime above a given value, using the given primality check
+pub fn next_prime_with(p: &mut Integer, check: &PrimalityCheck) {
     if p.is_even() {
         *p += 1;
     } else {
         *p += 2;
     }
 
-    while p.is_probably_prime(PRIME_CHECK_ITERS) == IsPrime::No {
+    let primes = small_primes();
+    let mut residues = sieve_residues(p, primes);
+    while !is_small_prime_candidate(p, primes) && (rejected_by_sieve(&residues) || !check.is_prime(p)) {
         *p += 2;
+        advance_sieve_residues(&mut residues, primes, 2);
     }
 }
 
-/// find next highest prime below a given value
-pub fn prev_prime(p: &mut Integer) {
+/// find next lowset prime above a given value
+pub fn next_prime(p: &mut Integer) {
+    next_prime_with(p, &PrimalityCheck::Bpsw);
+}
+
+/// find next highest prime below a given value, using the given primality check
+pub fn prev_prime_with(p: &mut Integer, check: &PrimalityCheck) {
     if p.is_even() {
         *p -= 1;
     } else {
         *p -= 2;
     }
 
-    while p.is_probably_prime(PRIME_CHECK_ITERS) == IsPrime::No {
+    let primes = small_primes();
+    let mut residues = sieve_residues(p, primes);
+    while !is_small_prime_candidate(p, primes) && (rejected_by_sieve(&residues) || !check.is_prime(p)) {
         *p -= 2;
+        advance_sieve_residues(&mut residues, primes, -2);
     }
 }
 
-/// generates largest prime number fitting into max_size_bytes that is congruent to 3 mod 4
-pub fn gen_largest_prime(max_size_bytes: usize) -> Integer {
+/// find next highest prime below a given value
+pub fn prev_prime(p: &mut Integer) {
+    prev_prime_with(p, &PrimalityCheck::Bpsw);
+}
+
+/// generates largest prime number fitting into max_size_bytes that is
+/// congruent to 3 mod 4, using the given primality check
+pub fn gen_largest_prime_with(max_size_bytes: usize, check: &PrimalityCheck) -> Integer {
     let mut prime = Integer::from(Integer::u_pow_u(2, (max_size_bytes * 8) as u32)) - 1;
-    prev_prime(&mut prime);
+    prev_prime_with(&mut prime, check);
     // ensure prime is congruent to 3 mod 4
     // as specified in paper
     while prime.mod_u(4) != 3 {
-        prev_prime(&mut prime);
+        prev_prime_with(&mut prime, check);
     }
     prime
 }
 
+/// generates largest prime number fitting into max_size_bytes that is congruent to 3 mod 4
+pub fn gen_largest_prime(max_size_bytes: usize) -> Integer {
+    gen_largest_prime_with(max_size_bytes, &PrimalityCheck::Bpsw)
+}
+
+/// generates the largest safe prime `p` (i.e. `(p - 1) / 2` is also prime)
+/// fitting into max_size_bytes that is congruent to 3 mod 4, using the given
+/// primality check
+///
+/// safe primes remove the small-subgroup edge cases that a merely-prime
+/// modulus can have, which is the modulus shape the sqrt-permutation
+/// literature recommends
+pub fn gen_safe_prime_with(max_size_bytes: usize, check: &PrimalityCheck) -> Integer {
+    let mut p: Integer = Integer::from(Integer::u_pow_u(2, (max_size_bytes * 8) as u32)) - 1;
+    if p.is_even() {
+        p -= 1;
+    }
+    while p.mod_u(4) != 3 {
+        p -= 2;
+    }
+    // q = (p - 1) / 2; scanning p downward by 4 keeps p odd, ≡ 3 mod 4, and q
+    // moving downward by 2 in lock-step
+    let mut q = Integer::from(&p - 1) / 2;
+
+    let primes = small_primes();
+    let mut p_residues = sieve_residues(&p, primes);
+    let mut q_residues = sieve_residues(&q, primes);
+
+    loop {
+        let p_candidate_ok = is_small_prime_candidate(&p, primes) || !rejected_by_sieve(&p_residues);
+        let q_candidate_ok = is_small_prime_candidate(&q, primes) || !rejected_by_sieve(&q_residues);
+        if p_candidate_ok && q_candidate_ok && check.is_prime(&q) && check.is_prime(&p) {
+            return p;
+        }
+
+        p -= 4;
+        q -= 2;
+        advance_sieve_residues(&mut p_residues, primes, -4);
+        advance_sieve_residues(&mut q_residues, primes, -2);
+    }
+}
+
+/// generates the largest safe prime fitting into max_size_bytes that is
+/// congruent to 3 mod 4
+pub fn gen_safe_prime(max_size_bytes: usize) -> Integer {
+    gen_safe_prime_with(max_size_bytes, &PrimalityCheck::Bpsw)
+}
+
+/// adapts any `rand_core` CSPRNG into a `rug::rand::ThreadRandGen`, so a
+/// caller-supplied generator can drive `rug`'s random integer routines
+/// instead of rug's own (non-cryptographic) default
+struct RngCoreBridge<'a, R: RngCore> {
+    rng: &'a mut R,
+}
+
+impl<R: RngCore> ThreadRandGen for RngCoreBridge<'_, R> {
+    fn gen(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+}
+
+/// draws a random prime of exactly `bits` bits using `rng` and the given
+/// primality `check`, forcing the top bit (so the result is exactly `bits`
+/// bits long) and the `p ≡ 3 (mod 4)` congruence required by
+/// [`sqrt_permutation`], then scans upward to the next prime satisfying it.
+/// Scanning is bounded to `[2^(bits-1), 2^bits)`: if it would run past
+/// `2^bits` (which would overrun the promised bit length), a fresh candidate
+/// is drawn instead of returning an oversized result.
+pub fn gen_prime_with<R: RngCore>(rng: &mut R, bits: u32, check: &PrimalityCheck) -> Integer {
+    let mut bridge = RngCoreBridge { rng };
+    let mut state = ThreadRandState::new_custom(&mut bridge);
+    let upper_bound = Integer::from(Integer::u_pow_u(2, bits));
+
+    'redraw: loop {
+        let mut candidate = Integer::from(Integer::random_bits(bits, &mut state));
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        while candidate.mod_u(4) != 3 {
+            candidate += 2;
+            if candidate >= upper_bound {
+                continue 'redraw;
+            }
+        }
+
+        if !check.is_prime(&candidate) {
+            loop {
+                next_prime_with(&mut candidate, check);
+                if candidate >= upper_bound {
+                    continue 'redraw;
+                }
+                if candidate.mod_u(4) == 3 {
+                    break;
+                }
+            }
+        }
+
+        return candidate;
+    }
+}
+
 /// performs sqrt permutation, serving as the "encode" stage
 /// returns perm: rug::Integer
 pub fn sqrt_permutation(input: &Integer, exp: &Integer, prime: &Integer) -> Integer {
@@ -141,32 +486,82 @@ pub fn inverse_sqrt(input: &Integer, prime: &Integer) -> Integer {
     result
 }
 
+/// chains [`sqrt_permutation`] `tau` times, feeding each round's output back
+/// in as the next round's input, as prescribed by Sloth. This is the slow
+/// "encode" half of the delay function: each round costs one `(p+1)/4`
+/// modular exponentiation.
+pub fn encode_iterated(block: &Integer, prime: &Integer, exp: &Integer, tau: u32) -> Integer {
+    let mut current = block.clone();
+    for _ in 0..tau {
+        current = sqrt_permutation(&current, exp, prime);
+    }
+    current
+}
 
-/// perform a basic run through of the block encoding pipeline
-/// 1. create the largest prime that is congruent to 3 mod 4
-/// 2. derive exponent from prime using e = (prime + 1) / 4
-/// 3. create a random block of data to be "encoded"
-/// 4. find the square root permutation ("encode" to cipher text)
-/// 5. find the inverse sqrt ("decode" back to plain text)
-/// 6. verify that the encoding preserved the data
-pub fn run() {
-    // generate largest prime
-    // create largest number possible for PRIME_BYTE_SIZE and then reduce
-    // until it is largest viable PRIME
-    let prime = gen_largest_prime(PRIME_BYTE_SIZE);
+/// applies [`inverse_sqrt`] `tau` times to recover the plaintext from
+/// [`encode_iterated`]'s output. This is the fast "decode" half of the delay
+/// function: each round costs one modular squaring, far cheaper than the
+/// exponentiation `encode_iterated` pays per round.
+pub fn decode_iterated(block: &Integer, prime: &Integer, tau: u32) -> Integer {
+    let mut current = block.clone();
+    for _ in 0..tau {
+        current = inverse_sqrt(&current, prime);
+    }
+    current
+}
 
+/// verifies that `output` is the result of running [`encode_iterated`] on
+/// `input` for `tau` rounds, by paying only the cheap decode side
+pub fn verify(input: &Integer, output: &Integer, prime: &Integer, tau: u32) -> bool {
+    decode_iterated(output, prime, tau) == *input
+}
+
+/// draws a random block using `rng`, so callers can make block generation
+/// reproducible with a seeded CSPRNG instead of `rand::random`'s thread RNG
+pub fn random_block_with<R: RngCore>(rng: &mut R) -> Block {
+    let mut block: Block = [0; BLOCK_BYTE_SIZE];
+    rng.fill_bytes(&mut block);
+    block
+}
+
+/// runs the block encoding pipeline over a caller-supplied `prime`, drawing
+/// all randomness from the supplied `rng` so the run is fully reproducible
+/// given a fixed seed
+/// 1. derive exponent from prime using e = (prime + 1) / 4
+/// 2. create a random block of data to be "encoded"
+/// 3. find the square root permutation ("encode" to cipher text)
+/// 4. find the inverse sqrt ("decode" back to plain text)
+/// 5. verify that the encoding preserved the data
+fn run_over<R: RngCore>(rng: &mut R, prime: Integer) {
     // e = (p + 1) / 4
     let mut exponent: Integer = prime.clone() + 1;
     exponent.div_exact_u_mut(4);
 
     // generate random number as input
-    let block_in: Block = rand::random();
+    let block_in: Block = random_block_with(rng);
     let int = from_block(block_in);
     let perm = sqrt_permutation(&int, &exponent, &prime);
     let inv = inverse_sqrt(&perm, &prime);
     let block_out = to_block(inv);
     assert_eq!(block_in, block_out);
-} 
+}
+
+/// perform a basic run through of the block encoding pipeline using the
+/// largest prime congruent to 3 mod 4 that fits in [`PRIME_BYTE_SIZE`] bytes
+pub fn run_with<R: RngCore>(rng: &mut R) {
+    run_over(rng, gen_largest_prime(PRIME_BYTE_SIZE));
+}
+
+/// convenience wrapper over [`run_with`] using the thread-local RNG
+pub fn run() {
+    run_with(&mut rand::thread_rng());
+}
+
+/// like [`run_with`], but operates over a safe prime modulus (see
+/// [`gen_safe_prime`]) instead of merely the largest prime
+pub fn run_with_safe_prime<R: RngCore>(rng: &mut R) {
+    run_over(rng, gen_safe_prime(PRIME_BYTE_SIZE));
+}
 
 // creating this submodule means we won't compile testing code
 // when we compile "production-ready" binaries
@@ -177,6 +572,11 @@ mod test {
     use rand;
     use rug::{integer::IsPrime, Integer};
 
+    // num iterations used in pysloth implementation (https://github.com/randomchain/pysloth/blob/master/sloth.c),
+    // kept here as a fixed round count for test assertions now that production code
+    // picks its own check via PrimalityCheck
+    const PRIME_CHECK_ITERS: u32 = 25;
+
     #[test]
     /// verify Block -> rug::Integer -> Block conversion
     fn test_conversion() {
@@ -222,4 +622,147 @@ mod test {
             run();
         }
     }
+
+    #[test]
+    /// verify that a fixed seed reproduces the same block and prime
+    fn test_deterministic_with_seed() {
+        use rand::SeedableRng;
+
+        let mut rng_a = rand::rngs::StdRng::seed_from_u64(42);
+        let mut rng_b = rand::rngs::StdRng::seed_from_u64(42);
+
+        let block_a = random_block_with(&mut rng_a);
+        let block_b = random_block_with(&mut rng_b);
+        assert_eq!(block_a, block_b);
+
+        let prime_a = gen_prime_with(&mut rng_a, 256, &PrimalityCheck::Bpsw);
+        let prime_b = gen_prime_with(&mut rng_b, 256, &PrimalityCheck::Bpsw);
+        assert_eq!(prime_a, prime_b);
+    }
+
+    #[test]
+    /// verify that gen_prime_with produces a prime of the requested bit length
+    fn test_gen_prime_with() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for bits in [64, 128, 256] {
+            let prime = gen_prime_with(&mut rng, bits, &PrimalityCheck::Bpsw);
+            assert_ne!(prime.is_probably_prime(PRIME_CHECK_ITERS), IsPrime::No);
+            assert_eq!(prime.significant_bits(), bits);
+            assert_eq!(prime.mod_u(4), 3);
+        }
+    }
+
+    #[test]
+    /// regression test for seeds that previously walked gen_prime_with's
+    /// upward prime scan straight past 2^bits, returning a prime one bit too wide
+    fn test_gen_prime_with_stays_within_bit_length() {
+        use rand::SeedableRng;
+
+        for seed in [20u64, 29, 92, 99, 173] {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let prime = gen_prime_with(&mut rng, 8, &PrimalityCheck::Bpsw);
+            assert_eq!(prime.significant_bits(), 8);
+        }
+    }
+
+    #[test]
+    /// verify is_prime_bpsw on known primes and composites
+    fn test_is_prime_bpsw() {
+        for p in [2u32, 3, 5, 7, 11, 101, 7919, 104729] {
+            assert!(is_prime_bpsw(&Integer::from(p)));
+        }
+        for c in [1u32, 4, 9, 15, 49, 561, 1105, 104730] {
+            assert!(!is_prime_bpsw(&Integer::from(c)));
+        }
+
+        // a base-2 strong pseudoprime (2047 = 23 * 89); the small-prime trial
+        // division rejects it before either probable-prime test runs
+        let pseudoprime = Integer::from(2047u32);
+        assert!(!is_prime_bpsw(&pseudoprime));
+
+        let prime = gen_largest_prime(BLOCK_BYTE_SIZE);
+        assert!(is_prime_bpsw(&prime));
+    }
+
+    #[test]
+    /// verify gen_safe_prime returns a prime p congruent to 3 mod 4 whose
+    /// (p - 1) / 2 is also prime
+    fn test_gen_safe_prime() {
+        for size in 1..16 {
+            let prime = gen_safe_prime(size);
+            assert!(is_prime_bpsw(&prime));
+            assert_eq!(prime.clone().mod_u(4), 3);
+
+            let q = Integer::from(&prime - 1) / 2;
+            assert!(is_prime_bpsw(&q));
+
+            let largest_value = Integer::from(Integer::u_pow_u(2, (size * 8) as u32)) - 1;
+            assert!(prime <= largest_value);
+        }
+    }
+
+    #[test]
+    /// verify end-to-end operation of the scheme over a safe-prime modulus
+    fn test_run_with_safe_prime() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        for _n in 0..5 {
+            run_with_safe_prime(&mut rng);
+        }
+    }
+
+    #[test]
+    /// verify mr_rounds_for_error_bound stays within the worst-case bound and
+    /// never drops below the 2-round floor
+    fn test_mr_rounds_for_error_bound() {
+        for bits in [64u32, 256, 512, 1024, 4096] {
+            for error_bits in [1u32, 40, 80, 128] {
+                let rounds = mr_rounds_for_error_bound(bits, error_bits);
+                assert!(rounds >= 2);
+                assert!(rounds <= error_bits.div_ceil(2).max(2));
+            }
+        }
+    }
+
+    #[test]
+    /// verify the MillerRabin paranoid-mode check threads through next_prime_with
+    /// and the safe-prime/_with generators the same way PrimalityCheck::Bpsw does
+    fn test_primality_check_miller_rabin() {
+        let rounds = mr_rounds_for_error_bound(256, 128);
+        let check = PrimalityCheck::MillerRabin(rounds);
+
+        let mut prime = gen_largest_prime_with(BLOCK_BYTE_SIZE, &check);
+        assert_ne!(prime.is_probably_prime(PRIME_CHECK_ITERS), IsPrime::No);
+        assert_eq!(prime.clone().mod_u(4), 3);
+
+        next_prime_with(&mut prime, &check);
+        assert_ne!(prime.is_probably_prime(PRIME_CHECK_ITERS), IsPrime::No);
+
+        let safe_prime = gen_safe_prime_with(16, &check);
+        assert_ne!(safe_prime.is_probably_prime(PRIME_CHECK_ITERS), IsPrime::No);
+        let q: Integer = Integer::from(&safe_prime - 1) / 2;
+        assert_ne!(q.is_probably_prime(PRIME_CHECK_ITERS), IsPrime::No);
+    }
+
+    #[test]
+    /// verify decode_iterated(encode_iterated(x)) == x for a range of tau
+    fn test_iterated_end_to_end() {
+        let prime = gen_largest_prime(PRIME_BYTE_SIZE);
+
+        let mut exponent: Integer = prime.clone() + 1;
+        exponent.div_exact_u_mut(4);
+
+        let block_in: Block = rand::random();
+        let int = from_block(block_in);
+
+        for tau in [1u32, 2, 5, 16] {
+            let encoded = encode_iterated(&int, &prime, &exponent, tau);
+            let decoded = decode_iterated(&encoded, &prime, tau);
+            assert_eq!(decoded, int);
+            assert!(verify(&int, &encoded, &prime, tau));
+        }
+    }
 }